@@ -0,0 +1,79 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::io;
+
+/// A coarse classification of the cause of a `getrandom` failure.
+///
+/// This lets callers distinguish transient conditions they may want to
+/// retry from permanent ones, without having to interpret OS-specific
+/// error codes themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The source of randomness exists but has not produced entropy yet
+    /// (e.g. the kernel CSPRNG is not seeded, or a non-blocking request
+    /// would otherwise block).
+    NotReady,
+    /// The source of randomness is permanently unavailable on this
+    /// system.
+    Unavailable,
+    /// Any other OS error that doesn't fall into the above categories.
+    Unexpected,
+}
+
+/// The error type for `getrandom`.
+///
+/// Carries an [`ErrorKind`] classifying the failure, along with the raw
+/// OS error code when one is available.
+#[derive(Clone, Copy)]
+pub struct Error {
+    kind: ErrorKind,
+    raw_os_error: Option<i32>,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, raw_os_error: Option<i32>) -> Error {
+        Error { kind, raw_os_error }
+    }
+
+    pub(crate) fn from_io(kind: ErrorKind, err: &io::Error) -> Error {
+        Error::new(kind, err.raw_os_error())
+    }
+
+    /// Returns the classification of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the raw OS error code, if one was available when this
+    /// error was constructed.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.raw_os_error
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Error")
+            .field("kind", &self.kind)
+            .field("raw_os_error", &self.raw_os_error)
+            .finish()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.raw_os_error {
+            Some(errno) => write!(f, "{:?} (os error {})", self.kind, errno),
+            None => write!(f, "{:?}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,33 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A small library for retrieving random data from the system or
+//! hardware RNG.
+
+mod error;
+mod random_device;
+mod utils;
+
+pub use error::{Error, ErrorKind};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[path = "linux_android.rs"]
+mod imp;
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+#[path = "solaris_illumos.rs"]
+mod imp;
+
+/// Fill `dest` with random bytes from the system's preferred random
+/// number source, blocking until it is ready if necessary.
+pub fn getrandom(dest: &mut [u8]) -> Result<(), Error> {
+    imp::getrandom(dest)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use imp::getrandom_nonblocking;
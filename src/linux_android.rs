@@ -9,7 +9,8 @@
 //! Implementation for Linux / Android
 extern crate libc;
 
-use super::Error;
+use super::random_device;
+use super::{Error, ErrorKind};
 use super::utils::use_init;
 use std::fs::File;
 use std::io;
@@ -21,45 +22,94 @@ static RNG_INIT: AtomicBool = ATOMIC_BOOL_INIT;
 
 enum RngSource {
     GetRandom,
-    Device(File),
+    Device,
 }
 
 thread_local!(
     static RNG_SOURCE: RefCell<Option<RngSource>> = RefCell::new(None);
 );
 
-fn syscall_getrandom(dest: &mut [u8]) -> Result<(), io::Error> {
-    let ret = unsafe {
-        libc::syscall(libc::SYS_getrandom, dest.as_mut_ptr(), dest.len(), 0)
-    };
-    if ret == -1 || ret != dest.len() as i64 {
-        return Err(io::Error::last_os_error());
+fn syscall_getrandom(dest: &mut [u8], flags: libc::c_uint) -> Result<(), io::Error> {
+    // Loop at least once even for an empty `dest`: callers (notably the
+    // `is_getrandom_available` probe) rely on a zero-length call still
+    // issuing the syscall so that `ENOSYS` is detected.
+    let mut pos = 0;
+    loop {
+        let ret = unsafe {
+            let ptr = dest[pos..].as_mut_ptr();
+            let len = dest[pos..].len();
+            libc::syscall(libc::SYS_getrandom, ptr, len, flags)
+        };
+        if ret == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        pos += ret as usize;
+        if pos >= dest.len() {
+            return Ok(());
+        }
+    }
+}
+
+// Classifies an OS error encountered while trying to obtain randomness,
+// preserving the raw errno for callers that need it for diagnostics.
+fn classify(err: io::Error) -> Error {
+    match err.raw_os_error() {
+        Some(libc::ENOSYS) => Error::from_io(ErrorKind::Unavailable, &err),
+        Some(libc::EAGAIN) => Error::from_io(ErrorKind::NotReady, &err),
+        _ => Error::from_io(ErrorKind::Unexpected, &err),
     }
-    Ok(())
 }
 
+/// Fill `dest` with random bytes, blocking until the OS RNG is ready if
+/// necessary.
 pub fn getrandom(dest: &mut [u8]) -> Result<(), Error> {
+    getrandom_impl(dest, 0)
+}
+
+/// Like [`getrandom`], but never blocks: if the OS RNG has not yet been
+/// seeded, this returns an [`Error`] of kind [`ErrorKind::NotReady`]
+/// instead of waiting. When the `getrandom` syscall is unavailable and
+/// `/dev/urandom` is used instead, its bytes are returned without
+/// waiting for the OS RNG to be seeded.
+pub fn getrandom_nonblocking(dest: &mut [u8]) -> Result<(), Error> {
+    getrandom_impl(dest, libc::GRND_NONBLOCK)
+}
+
+fn getrandom_impl(dest: &mut [u8], flags: libc::c_uint) -> Result<(), Error> {
+    let nonblocking = flags & libc::GRND_NONBLOCK != 0;
     RNG_SOURCE.with(|f| {
         use_init(f,
         || {
             let s = if is_getrandom_available() {
                 RngSource::GetRandom
             } else {
-                // read one byte from "/dev/random" to ensure that
-                // OS RNG has initialized
-                if !RNG_INIT.load(Ordering::Relaxed) {
-                    File::open("/dev/random")?.read_exact(&mut [0u8; 1])?;
-                    RNG_INIT.store(true, Ordering::Relaxed)
-                }
-                RngSource::Device(File::open("/dev/urandom")?)
+                RngSource::Device
             };
             Ok(s)
         }, |f| {
             match f {
-                RngSource::GetRandom => syscall_getrandom(dest),
-                RngSource::Device(f) => f.read_exact(dest),
+                RngSource::GetRandom => syscall_getrandom(dest, flags),
+                RngSource::Device => {
+                    // Read one byte from "/dev/random" to ensure that
+                    // the OS RNG has initialized; a non-blocking caller
+                    // opts out of this wait and takes whatever
+                    // `/dev/urandom` currently has instead. This is
+                    // checked on every call (cheaply, via the atomic)
+                    // rather than only at source-init time, since the
+                    // `RngSource` is cached per-thread and the first
+                    // call on a thread may have been non-blocking.
+                    if !nonblocking && !RNG_INIT.load(Ordering::Relaxed) {
+                        File::open("/dev/random")?.read_exact(&mut [0u8; 1])?;
+                        RNG_INIT.store(true, Ordering::Relaxed);
+                    }
+                    random_device::read(dest)
+                }
             }
-        }).map_err(|_| Error::Unknown)
+        }).map_err(classify)
     })
 }
 
@@ -71,9 +121,9 @@ fn is_getrandom_available() -> bool {
 
     CHECKER.call_once(|| {
         let mut buf: [u8; 0] = [];
-        let available = match syscall_getrandom(&mut buf) {
+        let available = match syscall_getrandom(&mut buf, libc::GRND_NONBLOCK) {
             Ok(()) => true,
-            Err(err) => err.raw_os_error() != Some(libc::ENOSYS),
+            Err(err) => classify(err).kind() != ErrorKind::Unavailable,
         };
         AVAILABLE.store(available, Ordering::Relaxed);
     });
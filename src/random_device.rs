@@ -0,0 +1,104 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Shared handling of a device-file fallback (e.g. `/dev/urandom`,
+//! `/dev/random`) for Unix backends whose random syscall is missing or
+//! unavailable.
+//!
+//! The device is opened lazily once per process, behind a `Once` (and
+//! reopened only to recover from `EBADF`), and
+//! the resulting `File` is shared across all threads through a `Mutex`
+//! rather than stashed in a `thread_local!`. This keeps a multithreaded
+//! process that falls back to the device from burning one file
+//! descriptor per thread, and gives every platform's device fallback
+//! the same `EBADF` recovery for free.
+extern crate libc;
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::Read;
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::{Mutex, Once, ONCE_INIT};
+
+static INIT: Once = ONCE_INIT;
+static mut FILE: *const Mutex<File> = 0 as *const Mutex<File>;
+static mut OPEN_ERRNO: i32 = 0;
+
+fn open(path: &str) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_CLOEXEC)
+        .open(path)
+}
+
+fn file(path: &str) -> io::Result<&'static Mutex<File>> {
+    INIT.call_once(|| unsafe {
+        match open(path) {
+            Ok(f) => FILE = Box::into_raw(Box::new(Mutex::new(f))),
+            Err(e) => OPEN_ERRNO = e.raw_os_error().unwrap_or(-1),
+        }
+    });
+    unsafe {
+        if FILE.is_null() {
+            Err(io::Error::from_raw_os_error(OPEN_ERRNO))
+        } else {
+            Ok(&*FILE)
+        }
+    }
+}
+
+/// Fills `dest` with bytes read from the shared `/dev/urandom` handle,
+/// opening it on first use and retrying the read on `EINTR`.
+///
+/// If the cached descriptor has been closed out from under us
+/// (e.g. by a library that aggressively manages file descriptors),
+/// reads fail with `EBADF`; in that case the device is transparently
+/// reopened and the read is retried once.
+pub fn read(dest: &mut [u8]) -> io::Result<()> {
+    read_path(dest, "/dev/urandom", usize::max_value())
+}
+
+/// Like [`read`], but reads from an arbitrary device `path`, splitting
+/// the request into chunks of at most `max_chunk` bytes per underlying
+/// `read(2)` call. Some devices (e.g. Solaris's `/dev/random`) refuse to
+/// hand back more than a fixed number of bytes per call.
+pub fn read_path(dest: &mut [u8], path: &str, max_chunk: usize) -> io::Result<()> {
+    let shared = file(path)?;
+    match read_exact_eintr(&mut shared.lock().unwrap(), dest, max_chunk) {
+        Err(ref e) if e.raw_os_error() == Some(libc::EBADF) => {}
+        result => return result,
+    }
+
+    let mut guard = shared.lock().unwrap();
+    *guard = open(path)?;
+    read_exact_eintr(&mut guard, dest, max_chunk)
+}
+
+// Like `Read::read_exact`, but retries the underlying read when it is
+// interrupted by a signal instead of giving up, and never asks for more
+// than `max_chunk` bytes in a single underlying `read(2)` call.
+fn read_exact_eintr(f: &mut File, mut dest: &mut [u8], max_chunk: usize) -> io::Result<()> {
+    while !dest.is_empty() {
+        let chunk_len = dest.len().min(max_chunk);
+        match f.read(&mut dest[..chunk_len]) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
+            Ok(n) => {
+                let tmp = dest;
+                dest = &mut tmp[n..];
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Implementation for Solaris / illumos
+//!
+//! Solaris 11.3 added a `getrandom` syscall, but illumos derivatives
+//! (illumos proper, SmartOS) may not have it, so we probe for it once
+//! and fall back to `/dev/random`, which on this platform is backed by
+//! Hash_DRBG/SHA-512 per NIST SP 800-90A (unlike `/dev/urandom`, which
+//! is only FIPS 186-2).
+extern crate libc;
+
+use super::random_device;
+use super::{Error, ErrorKind};
+use super::utils::use_init;
+use std::cell::RefCell;
+use std::io;
+use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
+
+// `libc` doesn't expose `SYS_getrandom` for these targets, so issue the
+// syscall with its raw number directly.
+const SYS_GETRANDOM: libc::c_long = 143;
+
+// Solaris's `/dev/random` refuses to hand back more than this many
+// bytes in a single `read`, so larger requests must be chunked.
+const DEV_RANDOM_CHUNK: usize = 1040;
+
+enum RngSource {
+    GetRandom,
+    Device,
+}
+
+thread_local!(
+    static RNG_SOURCE: RefCell<Option<RngSource>> = RefCell::new(None);
+);
+
+fn syscall_getrandom(dest: &mut [u8]) -> Result<(), io::Error> {
+    // Loop at least once even for an empty `dest`: the
+    // `is_getrandom_available` probe relies on a zero-length call still
+    // issuing the syscall so that `ENOSYS` is detected.
+    let mut pos = 0;
+    loop {
+        let ret = unsafe {
+            let ptr = dest[pos..].as_mut_ptr();
+            let len = dest[pos..].len();
+            libc::syscall(SYS_GETRANDOM, ptr, len, 0)
+        };
+        if ret == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        pos += ret as usize;
+        if pos >= dest.len() {
+            return Ok(());
+        }
+    }
+}
+
+// Classifies an OS error encountered while trying to obtain randomness,
+// preserving the raw errno for callers that need it for diagnostics.
+fn classify(err: io::Error) -> Error {
+    match err.raw_os_error() {
+        Some(libc::ENOSYS) => Error::from_io(ErrorKind::Unavailable, &err),
+        Some(libc::EAGAIN) => Error::from_io(ErrorKind::NotReady, &err),
+        _ => Error::from_io(ErrorKind::Unexpected, &err),
+    }
+}
+
+pub fn getrandom(dest: &mut [u8]) -> Result<(), Error> {
+    RNG_SOURCE.with(|f| {
+        use_init(f,
+        || {
+            let s = if is_getrandom_available() {
+                RngSource::GetRandom
+            } else {
+                RngSource::Device
+            };
+            Ok(s)
+        }, |f| {
+            match f {
+                RngSource::GetRandom => syscall_getrandom(dest),
+                RngSource::Device => {
+                    random_device::read_path(dest, "/dev/random", DEV_RANDOM_CHUNK)
+                }
+            }
+        }).map_err(classify)
+    })
+}
+
+fn is_getrandom_available() -> bool {
+    use std::sync::{Once, ONCE_INIT};
+
+    static CHECKER: Once = ONCE_INIT;
+    static AVAILABLE: AtomicBool = ATOMIC_BOOL_INIT;
+
+    CHECKER.call_once(|| {
+        let mut buf: [u8; 0] = [];
+        let available = match syscall_getrandom(&mut buf) {
+            Ok(()) => true,
+            Err(err) => classify(err).kind() != ErrorKind::Unavailable,
+        };
+        AVAILABLE.store(available, Ordering::Relaxed);
+    });
+
+    AVAILABLE.load(Ordering::Relaxed)
+}
@@ -0,0 +1,28 @@
+// Copyright 2018 Developers of the Rand project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cell::RefCell;
+use std::io;
+
+/// Lazily initializes a thread-local resource and then uses it.
+///
+/// `init` runs to populate `cell` on a call that finds it empty, so it
+/// runs once per thread unless it fails, in which case the error is
+/// returned and the next call runs it again; every call that has a
+/// cached value (including the first) then runs `f` against it.
+pub fn use_init<T, Init, F, R>(cell: &RefCell<Option<T>>, init: Init, f: F) -> io::Result<R>
+where
+    Init: FnOnce() -> io::Result<T>,
+    F: FnOnce(&mut T) -> io::Result<R>,
+{
+    if cell.borrow().is_none() {
+        *cell.borrow_mut() = Some(init()?);
+    }
+    let mut borrow = cell.borrow_mut();
+    f(borrow.as_mut().unwrap())
+}